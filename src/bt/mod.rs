@@ -11,12 +11,19 @@
 //! To use this strategy you need to implement the [`bt::State`][2] trait and
 //! everything else is handled for you.
 //!
+//! If the full backtracking tree ramifies too much to explore exhaustively,
+//! [`BeamAlgorithm`][3] trades completeness and optimality for a fixed memory
+//! bound.
+//!
 //! [1]: https://en.wikipedia.org/wiki/Backtracking
 //! [2]: trait.State.html
+//! [3]: struct.BeamAlgorithm.html
 
+use std::cmp::Ordering;
 use std::f64;
 use std::hash::Hash;
 use std::collections::HashSet;
+use std::rc::Rc;
 
 use super::Type;
 
@@ -82,6 +89,25 @@ pub trait State {
     /// `State::forward` method.
     fn backward(&mut self, a: Self::Alternative);
 
+    /// Whether this partial state could still lead to a valid solution.
+    ///
+    /// Checked right after every `State::forward`, before recursing into the
+    /// new state: if it returns `false` the branch is abandoned immediately,
+    /// without exploring its subtree. This is what brings constraint-style
+    /// pruning (n-queens, counting-down permutations...) to the algorithm
+    /// without having to invent a numeric `State::estimated_value` for a
+    /// problem that doesn't really have one.
+    ///
+    /// For the pruning to be sound you must keep the **monotonicity
+    /// invariant**: any extension of an infeasible partial state is also
+    /// infeasible. Once a partial assignment violates a constraint, taking
+    /// more alternatives can never repair it.
+    ///
+    /// Default is `true`, so by default nothing is pruned this way.
+    fn is_feasible(&self) -> bool {
+        true
+    }
+
     /// Current state's value.
     ///
     /// Only called when `State::is_final`, gives the algorithm information
@@ -126,11 +152,53 @@ pub trait State {
 /// hassle as you'll probably use `bool` or numbers and all those already
 /// implement the trait.
 ///
+/// # Examples
+///
+/// A single-branch chain a million steps deep, solved with both
+/// `should_continue` and `overflow_depth` bounding how far the search goes:
+///
+///     use strategies::bt::{Algorithm, State};
+///     use strategies::Type;
+///
+///     #[derive(Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+///     struct Countdown(u32);
+///
+///     impl State for Countdown {
+///         type Solution = u32;
+///         type Alternative = ();
+///
+///         fn problem_type(&self) -> Type { Type::All }
+///         fn size(&self) -> usize { self.0 as usize }
+///         fn alternatives(&self) -> Vec<()> { vec![()] }
+///         fn forward(&mut self, _a: ()) { self.0 -= 1; }
+///         fn backward(&mut self, _a: ()) { self.0 += 1; }
+///         fn value(&self) -> f64 { 0.0 }
+///         fn solution(self) -> Option<u32> { Some(self.0) }
+///     }
+///
+///     // `should_continue` is polled before every step, so a closure that
+///     // stops early aborts long before the million-deep chain bottoms out.
+///     let mut algo = Algorithm::new(Countdown(1_000_000)).should_continue(|| false);
+///     algo.solve();
+///     assert_eq!(algo.all_solutions().len(), 0);
+///
+///     // `overflow_depth` bounds how many `forward` calls a branch may take:
+///     // a 3-step chain needs at least `overflow_depth(3)` to be reached.
+///     let mut too_shallow = Algorithm::new(Countdown(3)).overflow_depth(2);
+///     too_shallow.solve();
+///     assert_eq!(too_shallow.all_solutions().len(), 0);
+///
+///     let mut deep_enough = Algorithm::new(Countdown(3)).overflow_depth(3);
+///     deep_enough.solve();
+///     assert_eq!(deep_enough.all_solutions().len(), 1);
+///
 /// [1]: trait.State.html
 pub struct Algorithm<S: State> {
     // The only 'option' to change a bit the algorithm's behaviour.
     // More could be added in the future.
     solution_count: usize,
+    should_continue: Rc<dyn Fn() -> bool>,
+    overflow_depth: usize,
 
     solutions: HashSet<S>,
     best_value: f64,
@@ -143,6 +211,8 @@ impl<S> Algorithm<S> where S: State + Clone + Eq + Hash + Ord {
     pub fn new(state: S) -> Self {
         Algorithm {
             solution_count: 100,
+            should_continue: Rc::new(|| true),
+            overflow_depth: usize::MAX,
 
             solutions: HashSet::new(),
             best_value: match state.problem_type() {
@@ -169,23 +239,107 @@ impl<S> Algorithm<S> where S: State + Clone + Eq + Hash + Ord {
         self
     }
 
+    /// Set a callback that is polled before every step the search takes
+    /// forward.
+    ///
+    /// As soon as it returns `false`, the search stops without exploring
+    /// any further: `solve` returns and `all_solutions` gives you whatever was
+    /// found up to that point. Use this for timeouts or to react to Ctrl-C.
+    ///
+    /// Default is to always continue.
+    pub fn should_continue<F>(mut self, f: F) -> Self where F: Fn() -> bool + 'static {
+        self.should_continue = Rc::new(f);
+        self
+    }
+
+    /// Bound the depth the search is allowed to reach.
+    ///
+    /// A branch may take at most `depth` `State::forward` calls. Once it
+    /// reaches that depth it goes no further: if the state there is not
+    /// `State::is_final`, the branch is abandoned without recording a
+    /// solution. This keeps the search from going forward forever on
+    /// problems whose `State::size` does not strictly decrease.
+    ///
+    /// Default is unbounded.
+    pub fn overflow_depth(mut self, depth: usize) -> Self {
+        self.overflow_depth = depth;
+        self
+    }
+
     /// All the solutions calculated with the algorithm.
     pub fn all_solutions(&self) -> HashSet<S> {
         self.solutions.clone()
     }
 
-    /// Store the current solution in the 'solutions' set if it's better than
-    /// any of the allready stored, or if the problem is of type 'All'.
-    fn update_solutions(&mut self) {
-        let value = self.state.value();
-        let problem_type = self.state.problem_type();
+    /// Solve the problem.
+    ///
+    /// After creating the `Algorithm` with a `State`, solve the problem so you
+    /// can get all the solutions.
+    pub fn solve(&mut self) {
+        let search = Search::new(self.state.clone(),
+                                  self.best_value,
+                                  self.should_continue.clone(),
+                                  self.overflow_depth);
 
-        if (problem_type != Type::Min && problem_type != Type::Max) ||
-           (problem_type == Type::Min && value < self.best_value) ||
-           (problem_type == Type::Max && value > self.best_value) {
+        for state in search {
+            self.best_value = state.value();
+            self.solutions.insert(state);
 
-            self.solutions.insert(self.state.clone());
-            self.best_value = value;
+            if self.solutions.len() >= self.solution_count {
+                self.success = true;
+                break;
+            }
+        }
+    }
+
+    /// Lazily walk the solutions to this problem, one at a time.
+    ///
+    /// Unlike `solve`, which explores the whole tree (up to `solution_count`
+    /// solutions) before returning, `sat_iter` suspends the search between
+    /// calls to `next`. This lets you write `algo.sat_iter().take(5)` or
+    /// `.find(|s| ...)` and only pay for as much of the tree as you actually
+    /// need.
+    pub fn sat_iter(self) -> impl Iterator<Item = S::Solution> {
+        Search::new(self.state, self.best_value, self.should_continue, self.overflow_depth)
+            .filter_map(|state| state.solution())
+    }
+}
+
+/// A single level of the explicit backtracking stack.
+///
+/// Keeps the alternatives still to be tried at this level, and the one
+/// currently applied to the state (so it can be undone with `State::backward`
+/// before trying the next one, or when popping back up).
+struct Frame<S: State> {
+    remaining: ::std::vec::IntoIter<S::Alternative>,
+    current: S::Alternative,
+}
+
+/// The backtracking engine, as an explicit state stack instead of a
+/// recursive `solve`.
+///
+/// Iterating it yields every `State` the search accepts as a new best
+/// solution (in the same order and under the same rules `Algorithm::solve`
+/// used to apply recursively), suspending the search between each `next()`
+/// instead of walking the whole tree up front.
+struct Search<S: State> {
+    should_continue: Rc<dyn Fn() -> bool>,
+    overflow_depth: usize,
+    best_value: f64,
+    state: S,
+    stack: Vec<Frame<S>>,
+    started: bool,
+}
+
+impl<S> Search<S> where S: State + Clone {
+    fn new(state: S, best_value: f64, should_continue: Rc<dyn Fn() -> bool>, overflow_depth: usize) -> Self {
+        Search {
+            should_continue: should_continue,
+            overflow_depth: overflow_depth,
+            best_value: best_value,
+            state: state,
+            stack: Vec::new(),
+            started: false,
         }
     }
 
@@ -200,31 +354,184 @@ impl<S> Algorithm<S> where S: State + Clone + Eq + Hash + Ord {
         }
     }
 
+    /// Push a frame for the current state's alternatives and descend into
+    /// the first feasible one. Returns `false` if none of them are.
+    fn descend(&mut self) -> bool {
+        let alternatives = self.state
+            .alternatives()
+            .into_iter()
+            .filter(|a| !self.is_to_prune(a.clone()))
+            .collect::<Vec<S::Alternative>>();
+
+        let mut remaining = alternatives.into_iter();
+
+        while let Some(a) = remaining.next() {
+            self.state.forward(a.clone());
+
+            if self.state.is_feasible() {
+                self.stack.push(Frame { remaining: remaining, current: a });
+                return true;
+            }
+
+            self.state.backward(a);
+        }
+
+        false
+    }
+
+    /// Undo the deepest frame's alternative and try its next feasible
+    /// sibling, or pop up another level when there isn't one. Returns
+    /// `false` once the whole tree has been exhausted.
+    fn backtrack(&mut self) -> bool {
+        while let Some(mut frame) = self.stack.pop() {
+            self.state.backward(frame.current.clone());
+
+            while let Some(a) = frame.remaining.next() {
+                self.state.forward(a.clone());
+
+                if self.state.is_feasible() {
+                    frame.current = a;
+                    self.stack.push(frame);
+                    return true;
+                }
+
+                self.state.backward(a);
+            }
+        }
+
+        false
+    }
+}
+
+impl<S> Iterator for Search<S> where S: State + Clone {
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        loop {
+            if !(self.should_continue)() {
+                return None;
+            }
+
+            if self.started {
+                if !self.backtrack() {
+                    return None;
+                }
+            } else {
+                self.started = true;
+            }
+
+            while !self.state.is_final() && self.stack.len() < self.overflow_depth {
+                if !(self.should_continue)() {
+                    return None;
+                }
+
+                if !self.descend() {
+                    break;
+                }
+            }
+
+            if self.state.is_final() {
+                let value = self.state.value();
+                let problem_type = self.state.problem_type();
+
+                let better = (problem_type != Type::Min && problem_type != Type::Max) ||
+                    (problem_type == Type::Min && value < self.best_value) ||
+                    (problem_type == Type::Max && value > self.best_value);
+
+                if better {
+                    self.best_value = value;
+                    return Some(self.state.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A level-synchronous beam search.
+///
+/// # Explanation
+///
+/// Where [`Algorithm`][1] explores the whole tree (pruning only what
+/// `State::estimated_value` and `State::is_feasible` rule out),
+/// `BeamAlgorithm` bounds the memory it uses: it keeps a _frontier_ of at most `β` states, and at each round
+/// replaces it with the `β` best children of the current frontier, scored
+/// with `State::estimated_value`. Infeasible children (`State::is_feasible`
+/// returning `false`) are dropped before that cut is made, so they never
+/// crowd out a feasible state's beam slot. States that don't make the cut
+/// are discarded for good, so **beam search is neither complete nor
+/// optimal** - a final state reachable only through a discarded branch will
+/// never be found. What you get in exchange is a fixed memory bound of `β`
+/// states per level, which matters for problems whose tree is too large to
+/// hold in memory at all.
+///
+/// # Usage
+///
+/// Same as [`Algorithm`][1]: implement a [`State`][2], then solve it. Your
+/// `State` only needs to be `Clone` here, none of `Algorithm`'s other bounds
+/// apply since there's no `HashSet` of solutions to deduplicate.
+///
+/// [1]: struct.Algorithm.html
+/// [2]: trait.State.html
+pub struct BeamAlgorithm<S: State> {
+    beta: usize,
+    frontier: Vec<S>,
+    solution: Option<S>,
+}
+
+impl<S> BeamAlgorithm<S> where S: State + Clone {
+    /// Create a new beam search to solve `state`, keeping at most `beta`
+    /// states per level.
+    pub fn new(state: S, beta: usize) -> Self {
+        BeamAlgorithm {
+            beta: beta,
+            frontier: vec![state],
+            solution: None,
+        }
+    }
+
     /// Solve the problem.
     ///
-    /// After creating the `Algorithm` with a `State`, solve the problem so you
-    /// can get all the solutions.
+    /// Stops as soon as a final state surfaces in the frontier, or once the
+    /// frontier empties because every branch got pruned away.
     pub fn solve(&mut self) {
-        if self.state.is_final() {
-            self.update_solutions();
-            self.success = self.solutions.len() >= self.solution_count;
-
-        } else {
-            let alternatives = self.state
-                .alternatives()
-                .into_iter()
-                .filter(|a| !self.is_to_prune(a.clone()))
-                .collect::<Vec<S::Alternative>>();
-
-            for alternative in alternatives {
-                self.state.forward(alternative.clone());
-                self.solve();
-                self.state.backward(alternative.clone());
-
-                if self.success {
-                    break;
-                }
+        while !self.frontier.is_empty() {
+            if let Some(index) = self.frontier.iter().position(|s| s.is_final()) {
+                self.solution = Some(self.frontier.swap_remove(index));
+                return;
             }
+
+            let problem_type = self.frontier[0].problem_type();
+
+            let mut children = self.frontier
+                .iter()
+                .flat_map(|state| {
+                    state.alternatives()
+                        .into_iter()
+                        .map(|a| {
+                            let score = state.estimated_value(a.clone());
+                            let mut child = state.clone();
+                            child.forward(a);
+                            (score, child)
+                        })
+                        .filter(|&(_, ref child)| child.is_feasible())
+                        .collect::<Vec<(f64, S)>>()
+                })
+                .collect::<Vec<(f64, S)>>();
+
+            children.sort_by(|a, b| {
+                match problem_type {
+                    Type::Max => b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal),
+                    _ => a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal),
+                }
+            });
+            children.truncate(self.beta);
+
+            self.frontier = children.into_iter().map(|(_, s)| s).collect();
         }
     }
+
+    /// The solution found, if any.
+    pub fn get_solution(self) -> Option<S::Solution> {
+        self.solution.and_then(|s| s.solution())
+    }
 }
@@ -13,9 +13,11 @@
 //! [1]: https://en.wikipedia.org/wiki/Divide_and_conquer_algorithms
 //! [2]: trait.DacProblem.html
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::rc::Rc;
 
 /// Divide & Conquer Problem.
 ///
@@ -85,6 +87,12 @@ use std::marker::PhantomData;
 ///         fn get_solution(&self, partial_solution: &u64) -> Option<u64> {
 ///             Some(*partial_solution)
 ///         }
+///
+///         // Fibonacci's subproblem graph is a tree, not a cycle, so this
+///         // is never actually used.
+///         fn cyclic_default(&self) -> u64 {
+///             0
+///         }
 ///     }
 ///
 ///     // Now we can use DacAlgorithm...
@@ -138,6 +146,17 @@ pub trait DacProblem<S, E> {
     /// Transforms the partial solution `E`, if it is possible, into a final
     /// solution `S`.
     fn get_solution(&self, partial_solution: &E) -> Option<S>;
+
+    /// A provisional value for this problem, used when it is reached again
+    /// while it is already being solved higher up the call stack (i.e. the
+    /// subproblem graph has a cycle back to it).
+    ///
+    /// Only [`DacMemAlgorithm`][1]'s cycle-safe tabling calls this;
+    /// `DacAlgorithm` never will, since a plain recursive solve would simply
+    /// never terminate on a cyclic subproblem graph in the first place.
+    ///
+    /// [1]: struct.DacMemAlgorithm.html#cycle-safety
+    fn cyclic_default(&self) -> E;
 }
 
 /// The problem solver.
@@ -154,33 +173,62 @@ pub trait DacProblem<S, E> {
 pub struct DacAlgorithm<P, S, E>
     where P: DacProblem<S, E>
 {
-    phan: PhantomData<S>,
-    partial_solution: E,
+    phan: PhantomData<(S, E)>,
     problem: P,
+    should_continue: Rc<dyn Fn() -> bool>,
+    overflow_depth: usize,
+    partial_solution: RefCell<Option<E>>,
 }
 
 impl<P, S, E> DacAlgorithm<P, S, E>
-    where P: DacProblem<S, E>
+    where P: DacProblem<S, E>, E: Clone
 {
-    /// Solve the `problem` problem.
+    /// Prepare `problem` to be solved.
     pub fn new(problem: P) -> Self {
-        let e = Self::solve(&problem);
-
         DacAlgorithm {
             phan: PhantomData,
-            partial_solution: e,
             problem: problem,
+            should_continue: Rc::new(|| true),
+            overflow_depth: usize::MAX,
+            partial_solution: RefCell::new(None),
         }
     }
 
-    fn solve(problem: &P) -> E {
-        if problem.is_base_case() {
+    /// Set a callback that is polled at the start of every recursive step.
+    ///
+    /// As soon as it returns `false`, the recursion unwinds early: every
+    /// subproblem reached from then on is treated as if it were a base case
+    /// (its `DacProblem::base_case_solution` is used instead of splitting
+    /// it), and the branches still on the stack combine those values, so
+    /// `get_solution` still gives you a (possibly approximate) result
+    /// instead of nothing. Use this for timeouts or to react to Ctrl-C.
+    ///
+    /// Default is to always continue.
+    pub fn should_continue<F>(mut self, f: F) -> Self where F: Fn() -> bool + 'static {
+        self.should_continue = Rc::new(f);
+        self
+    }
+
+    /// Bound the recursion depth the algorithm is allowed to reach.
+    ///
+    /// Once a branch goes past `depth`, it is treated as a base case instead
+    /// of recursing further. This protects against a stack overflow on
+    /// problems whose `DacProblem::size` does not strictly decrease.
+    ///
+    /// Default is unbounded.
+    pub fn overflow_depth(mut self, depth: usize) -> Self {
+        self.overflow_depth = depth;
+        self
+    }
+
+    fn solve(&self, problem: &P, depth: usize) -> E {
+        if problem.is_base_case() || depth > self.overflow_depth || !(self.should_continue)() {
             problem.base_case_solution()
 
         } else {
             let solutions = (0..problem.subproblem_count())
                                 .map(|i| problem.get_subproblem(i))
-                                .map(|p| Self::solve(&p))
+                                .map(|p| self.solve(&p, depth + 1))
                                 .collect::<Vec<E>>();
 
             problem.combine(solutions)
@@ -188,8 +236,18 @@ impl<P, S, E> DacAlgorithm<P, S, E>
     }
 
     /// Get the final solution.
+    ///
+    /// The search runs on the first call and its result is cached, so later
+    /// calls return the same answer even if `should_continue` is a stateful
+    /// closure that would behave differently on a second run.
     pub fn get_solution(&self) -> Option<S> {
-        self.problem.get_solution(&self.partial_solution)
+        if self.partial_solution.borrow().is_none() {
+            let e = self.solve(&self.problem, 0);
+            *self.partial_solution.borrow_mut() = Some(e);
+        }
+
+        let partial_solution = self.partial_solution.borrow();
+        self.problem.get_solution(partial_solution.as_ref().unwrap())
     }
 }
 
@@ -219,13 +277,88 @@ impl<P, S, E> DacAlgorithm<P, S, E>
 ///     - `Eq` (and therefore `PartialEq`)
 ///     - `Hash`
 ///     - `Clone`
-/// - `E` has to be `Clone` too.
+/// - `E` has to be `Clone` and `PartialEq` too.
 ///
 /// Which is not complicated to do:
 ///
 ///     #[derive(Eq, PartialEq, Hash, Clone)]
 ///     struct Fibonacci(u64);
 ///
+/// # Cycle safety
+///
+/// `DacProblem::get_subproblem` usually describes a tree, but nothing stops
+/// it from describing a graph where a subproblem can (transitively) reach
+/// itself. A naive memoized solve would recurse forever the first time it
+/// revisits a problem that is still being solved higher up the call stack.
+///
+/// Instead, `DacMemAlgorithm` tracks which problems are currently in
+/// progress, and hands out `DacProblem::cyclic_default` as a stand-in value
+/// the moment one of them is reached again. Since that provisional value can
+/// make some of the combined results wrong, the table is then refined by
+/// recombining every tabled entry from its (by-then-cached) subproblems,
+/// repeating until nothing changes anymore. This only runs when a cycle was
+/// actually found, so acyclic problems pay no extra cost; it also only
+/// terminates if `DacProblem::combine` is **monotone** - a more accurate
+/// subproblem value must never make a dependent's combined value move away
+/// from its true one, or the fixpoint could oscillate forever.
+///
+/// For example, a shortest-path graph with a cycle between `A` and `B`:
+///
+///     use strategies::dac::{DacProblem, DacMemAlgorithm};
+///
+///     #[derive(Clone, Eq, PartialEq, Hash)]
+///     enum Node { A, B, Goal }
+///
+///     // A -- 3 --> B -- 2 --> Goal
+///     // B -- 1 --> A -- 10 --> Goal
+///     impl DacProblem<f64, f64> for Node {
+///         fn size(&self) -> usize { 0 }
+///
+///         fn is_base_case(&self) -> bool {
+///             match *self { Node::Goal => true, _ => false }
+///         }
+///
+///         fn base_case_solution(&self) -> f64 { 0.0 }
+///
+///         fn subproblem_count(&self) -> usize {
+///             match *self { Node::Goal => 0, _ => 2 }
+///         }
+///
+///         fn get_subproblem(&self, i: usize) -> Node {
+///             match (self, i) {
+///                 (&Node::A, 0) => Node::B,
+///                 (&Node::A, _) => Node::Goal,
+///                 (&Node::B, 0) => Node::A,
+///                 (&Node::B, _) => Node::Goal,
+///                 (&Node::Goal, _) => unreachable!(),
+///             }
+///         }
+///
+///         // The distance via each neighbour, kept to the shortest one.
+///         fn combine(&self, solutions: Vec<f64>) -> f64 {
+///             match *self {
+///                 Node::A => f64::min(solutions[0] + 3.0, solutions[1] + 10.0),
+///                 Node::B => f64::min(solutions[0] + 1.0, solutions[1] + 2.0),
+///                 Node::Goal => unreachable!(),
+///             }
+///         }
+///
+///         fn get_solution(&self, partial_solution: &f64) -> Option<f64> {
+///             Some(*partial_solution)
+///         }
+///
+///         // An unknown distance starts out as "infinitely far", so it can
+///         // only ever be improved on once the real value is tabled.
+///         fn cyclic_default(&self) -> f64 {
+///             f64::MAX
+///         }
+///     }
+///
+///     // A -> B -> Goal (3 + 2) beats A -> Goal (10) directly.
+///     assert_eq!(DacMemAlgorithm::new(Node::A).get_solution(), Some(5.0));
+///     // B -> Goal (2) beats B -> A -> Goal (1 + 10).
+///     assert_eq!(DacMemAlgorithm::new(Node::B).get_solution(), Some(2.0));
+///
 /// [1]: https://en.wikipedia.org/wiki/Fibonacci_number
 /// [2]: struct.DacAlgorithm.html#usage
 pub struct DacMemAlgorithm<P, S, E>
@@ -239,38 +372,94 @@ pub struct DacMemAlgorithm<P, S, E>
 
 impl<P, S, E> DacMemAlgorithm<P, S, E>
     where P: DacProblem<S, E> + Eq + Hash + Clone,
-          E: Clone
+          E: Clone + PartialEq
 {
     pub fn new(problem: P) -> Self {
-        let mut map = HashMap::new();
-        Self::solve(&problem, &mut map);
+        let mut solutions = HashMap::new();
+        let mut in_progress = HashSet::new();
+        let mut cycled = false;
+
+        Self::solve(&problem, &mut solutions, &mut in_progress, &mut cycled);
+
+        if cycled {
+            Self::fixpoint(&mut solutions);
+        }
 
         DacMemAlgorithm {
             phan: PhantomData,
-            solutions: map,
+            solutions: solutions,
             problem: problem,
         }
     }
 
-    fn solve(problem: &P, mut solutions: &mut HashMap<P, E>) -> E {
+    fn solve(problem: &P,
+             mut solutions: &mut HashMap<P, E>,
+             mut in_progress: &mut HashSet<P>,
+             cycled: &mut bool)
+             -> E {
         if problem.is_base_case() {
             problem.base_case_solution()
 
-        } else if solutions.contains_key(&problem) {
-            solutions.get(&problem).unwrap().clone()
+        } else if let Some(e) = solutions.get(problem) {
+            e.clone()
+
+        } else if in_progress.contains(problem) {
+            *cycled = true;
+            problem.cyclic_default()
 
         } else {
+            in_progress.insert(problem.clone());
+
             let solution = {
                 let solutions = (0..problem.subproblem_count())
                                     .map(|i| problem.get_subproblem(i))
-                                    .map(|p| Self::solve(&p, &mut solutions))
+                                    .map(|p| Self::solve(&p, &mut solutions, &mut in_progress, cycled))
                                     .collect::<Vec<E>>();
 
                 problem.combine(solutions)
             };
 
-            solutions.insert(problem.clone(), solution);
-            solutions.get(&problem).unwrap().clone()
+            in_progress.remove(problem);
+            solutions.insert(problem.clone(), solution.clone());
+            solution
+        }
+    }
+
+    /// Recombine every tabled problem from its subproblems' current values,
+    /// until none of them change anymore.
+    fn fixpoint(solutions: &mut HashMap<P, E>) {
+        loop {
+            let mut changed = false;
+
+            for problem in solutions.keys().cloned().collect::<Vec<P>>() {
+                let sub_solutions = (0..problem.subproblem_count())
+                                        .map(|i| problem.get_subproblem(i))
+                                        .map(|p| Self::lookup(&p, solutions))
+                                        .collect::<Vec<E>>();
+
+                let recombined = problem.combine(sub_solutions);
+
+                if solutions.get(&problem) != Some(&recombined) {
+                    solutions.insert(problem, recombined);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// The best known value for `problem`, without recursing: its base case,
+    /// its tabled value, or `DacProblem::cyclic_default` if neither applies.
+    fn lookup(problem: &P, solutions: &HashMap<P, E>) -> E {
+        if problem.is_base_case() {
+            problem.base_case_solution()
+        } else {
+            solutions.get(problem)
+                .cloned()
+                .unwrap_or_else(|| problem.cyclic_default())
         }
     }
 
@@ -5,6 +5,7 @@
 
 pub mod bt;
 pub mod dac;
+pub mod search;
 
 /// Basic problem type: maximization, minimization or all.
 ///
@@ -0,0 +1,226 @@
+//! The informed search strategy (A*).
+//!
+//! From [Wikipedia][1]:
+//!
+//! > A* is a graph traversal and path search algorithm, which is used in many
+//! > fields of computer science due to its completeness, optimality, and
+//! > optimal efficiency. ... At each iteration ... A* selects the path that
+//! > minimizes `f(n) = g(n) + h(n)`.
+//!
+//! To use this strategy you need to implement the [`search::State`][2] trait
+//! and everything else is handled for you. Costs are always minimized,
+//! following the crate's `Type::Min` convention; if you need the plain
+//! shortest-path algorithm (Dijkstra), leave `State::heuristic` at its
+//! default of `0.0`.
+//!
+//! [1]: https://en.wikipedia.org/wiki/A*_search_algorithm
+//! [2]: trait.State.html
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// Informed search problem state.
+///
+/// In order to use this strategy you have to implement this trait. There is
+/// one generic type:
+///
+/// * `Action`: What led from the previous state to this one. It is returned
+///   alongside the path so you know which moves to make.
+///
+/// Unlike [`bt::State`][1], a `search::State` doesn't hold the problem while
+/// it runs: each call to `State::successors` produces brand new states, and
+/// the algorithm keeps track of the rest (the open set, the best known costs,
+/// the path taken).
+///
+/// [1]: ../bt/trait.State.html
+pub trait State: Sized {
+    type Action;
+
+    /// The states reachable from this one.
+    ///
+    /// For every successor, return the `Action` that reaches it, the
+    /// successor itself, and the cost of taking that single step (must be
+    /// non-negative).
+    fn successors(&self) -> Vec<(Self::Action, Self, f64)>;
+
+    /// Whether this state is a goal.
+    fn is_goal(&self) -> bool;
+
+    /// An estimation of the remaining cost to reach a goal from this state.
+    ///
+    /// For the algorithm to be optimal, this heuristic must be *admissible*:
+    /// it must never overestimate the true remaining cost. An admissible
+    /// heuristic of `0.0` (the default) turns this strategy into plain
+    /// Dijkstra, which is always admissible but explores more states than a
+    /// tighter, still-admissible estimate would.
+    fn heuristic(&self) -> f64 {
+        0.0
+    }
+}
+
+/// A node waiting to be expanded, ordered by `f(n) = g(n) + h(n)`.
+///
+/// `BinaryHeap` is a max-heap, so the ordering is reversed to turn it into
+/// the min-heap the open set needs.
+struct Node<St> {
+    state: St,
+    g: f64,
+    f: f64,
+}
+
+impl<St> PartialEq for Node<St> {
+    fn eq(&self, other: &Self) -> bool {
+        self.f == other.f
+    }
+}
+
+impl<St> Eq for Node<St> {}
+
+impl<St> PartialOrd for Node<St> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.f.partial_cmp(&self.f)
+    }
+}
+
+impl<St> Ord for Node<St> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// The problem solver.
+///
+/// Solves an informed search problem with A*. The open set is a binary heap
+/// ordered by `f(n)`, and a `HashMap` keeps the best known `g(n)` (the
+/// accumulated path cost) per state, together with a parent pointer so the
+/// path can be reconstructed once a goal is found.
+///
+/// # Usage
+///
+/// First, implement a [`search::State`][1]. Then `solve` it.
+///
+/// Your state, `St`, has to implement some traits:
+///
+/// * `Clone`
+/// * `Eq` (and `PartialEq`)
+/// * `Hash`
+///
+/// # Examples
+///
+/// A small graph where the cheapest route isn't the one first discovered:
+/// `Start` reaches `Goal` directly via `A` at cost 11, but detouring through
+/// `B` first brings it down to 3. The search has to relax `B` and `Goal`
+/// to a cheaper `g(n)` after they were already reached once.
+///
+///     use strategies::search::{State, Algorithm};
+///
+///     #[derive(Clone, Eq, PartialEq, Hash)]
+///     enum Node { Start, A, B, Goal }
+///
+///     impl State for Node {
+///         type Action = &'static str;
+///
+///         fn successors(&self) -> Vec<(&'static str, Node, f64)> {
+///             match *self {
+///                 Node::Start => vec![("Start->A", Node::A, 1.0), ("Start->B", Node::B, 4.0)],
+///                 Node::A => vec![("A->B", Node::B, 1.0), ("A->Goal", Node::Goal, 10.0)],
+///                 Node::B => vec![("B->Goal", Node::Goal, 1.0)],
+///                 Node::Goal => vec![],
+///             }
+///         }
+///
+///         fn is_goal(&self) -> bool {
+///             *self == Node::Goal
+///         }
+///
+///         // The exact remaining cost to `Goal`, which is always admissible.
+///         fn heuristic(&self) -> f64 {
+///             match *self {
+///                 Node::Start => 3.0,
+///                 Node::A => 2.0,
+///                 Node::B => 1.0,
+///                 Node::Goal => 0.0,
+///             }
+///         }
+///     }
+///
+///     let (path, cost) = Algorithm::new(Node::Start).solve().unwrap();
+///     assert_eq!(path, vec!["Start->A", "A->B", "B->Goal"]);
+///     assert_eq!(cost, 3.0);
+///
+/// [1]: trait.State.html
+pub struct Algorithm<St: State> {
+    start: St,
+}
+
+impl<St> Algorithm<St>
+    where St: State + Clone + Eq + Hash
+{
+    /// Create a new algorithm to solve starting from `state`.
+    pub fn new(state: St) -> Self {
+        Algorithm { start: state }
+    }
+
+    /// Solve the problem.
+    ///
+    /// Returns the sequence of actions that leads from the initial state to
+    /// a goal, together with the total cost, or `None` if no goal is
+    /// reachable.
+    pub fn solve(&self) -> Option<(Vec<St::Action>, f64)> {
+        let mut best_g: HashMap<St, f64> = HashMap::new();
+        let mut came_from: HashMap<St, (St, St::Action)> = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        best_g.insert(self.start.clone(), 0.0);
+        open.push(Node {
+            f: self.start.heuristic(),
+            g: 0.0,
+            state: self.start.clone(),
+        });
+
+        while let Some(Node { state, g, .. }) = open.pop() {
+            if g > *best_g.get(&state).unwrap_or(&f64::MAX) {
+                // A cheaper path to this state was found after it was
+                // pushed; skip this stale entry.
+                continue;
+            }
+
+            if state.is_goal() {
+                let cost = g;
+                return Some((Self::reconstruct(came_from, state), cost));
+            }
+
+            for (action, successor, step_cost) in state.successors() {
+                let tentative_g = g + step_cost;
+
+                if tentative_g < *best_g.get(&successor).unwrap_or(&f64::MAX) {
+                    best_g.insert(successor.clone(), tentative_g);
+                    came_from.insert(successor.clone(), (state.clone(), action));
+
+                    open.push(Node {
+                        f: tentative_g + successor.heuristic(),
+                        g: tentative_g,
+                        state: successor,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Walk the parent pointers back from `state` to the initial state,
+    /// collecting the actions taken along the way.
+    fn reconstruct(mut came_from: HashMap<St, (St, St::Action)>, mut state: St) -> Vec<St::Action> {
+        let mut actions = Vec::new();
+
+        while let Some((parent, action)) = came_from.remove(&state) {
+            actions.push(action);
+            state = parent;
+        }
+
+        actions.reverse();
+        actions
+    }
+}